@@ -0,0 +1,91 @@
+use log::warn;
+use rppal::gpio::{Gpio, OutputPin};
+use syact::prelude::*;
+
+/// Drives MS1/MS2/MS3 to the A4988 truth-table combination for `micro`, configuring
+/// microstepping in hardware instead of just in software.
+fn write_microstep_pins(pins : &mut [OutputPin; 3], micro : MicroSteps) {
+    let (ms1, ms2, ms3) = match micro.0 {
+        1 => (false, false, false),
+        2 => (true, false, false),
+        4 => (false, true, false),
+        8 => (true, true, false),
+        16 => (true, true, true),
+        other => {
+            warn!("> {} microsteps has no MS1/MS2/MS3 combination on this driver, defaulting to full step", other);
+            (false, false, false)
+        }
+    };
+
+    for (pin, high) in pins.iter_mut().zip([ms1, ms2, ms3]) {
+        if high { pin.set_high() } else { pin.set_low() }
+    }
+}
+
+/// Owns the enable and MS1/MS2/MS3 microstep-select pins of an A4988 style
+/// stepper driver, plus the step/dir pins that eventually become a [`GenericPWM`].
+///
+/// The MS1/MS2/MS3 truth table in [`write_microstep_pins`] is the A4988's; a
+/// DRV8825 assigns `111` to 1/32 microstepping rather than 1/16, so wiring one up
+/// here would silently drive the wrong physical step size.
+pub struct A4988Driver {
+    pin_step : OutputPin,
+    pin_dir : OutputPin,
+    pin_en : OutputPin,
+    pin_ms : [OutputPin; 3],
+}
+
+impl A4988Driver {
+    pub fn new(
+        gpio : &Gpio,
+        pin_step : u8,
+        pin_dir : u8,
+        pin_en : u8,
+        pin_ms : [u8; 3]
+    ) -> Result<Self, rppal::gpio::Error> {
+        Ok(Self {
+            pin_step: gpio.get(pin_step)?.into_output(),
+            pin_dir: gpio.get(pin_dir)?.into_output(),
+            pin_en: gpio.get(pin_en)?.into_output(),
+            pin_ms: [
+                gpio.get(pin_ms[0])?.into_output(),
+                gpio.get(pin_ms[1])?.into_output(),
+                gpio.get(pin_ms[2])?.into_output(),
+            ]
+        })
+    }
+
+    /// Splits the driver into a [`GenericPWM`] for `Stepper::new` (step/dir) and an
+    /// [`A4988Aux`] handle for the enable/microstep-select pins, which the caller
+    /// keeps around to control power and hardware microstepping around the move.
+    pub fn into_parts(self, micro : MicroSteps) -> Result<(GenericPWM, A4988Aux), syact::Error> {
+        let mut aux = A4988Aux { pin_en: self.pin_en, pin_ms: self.pin_ms };
+        aux.set_microsteps_hw(micro);
+
+        Ok((GenericPWM::new(self.pin_step, self.pin_dir)?, aux))
+    }
+}
+
+/// The enable/microstep-select half of an [`A4988Driver`], retained after
+/// [`A4988Driver::into_parts`] so the motor can still be enabled/disabled and
+/// re-microstepped once its step/dir pins belong to a `GenericPWM`/`Stepper`.
+pub struct A4988Aux {
+    pin_en : OutputPin,
+    pin_ms : [OutputPin; 3],
+}
+
+impl A4988Aux {
+    /// Energizes the coils (the enable line is active-low).
+    pub fn enable(&mut self) {
+        self.pin_en.set_low();
+    }
+
+    /// De-energizes the coils between moves to save power and reduce heat.
+    pub fn disable(&mut self) {
+        self.pin_en.set_high();
+    }
+
+    pub fn set_microsteps_hw(&mut self, micro : MicroSteps) {
+        write_microstep_pins(&mut self.pin_ms, micro);
+    }
+}
@@ -0,0 +1,112 @@
+use clap::{command, arg, value_parser};
+use log::{debug, info};
+use rppal::gpio::Gpio;
+use syact::prelude::*;
+
+#[path = "motion.rs"]
+mod motion;
+
+use motion::{Axis, TrapezoidalProfile, drive_coordinated};
+
+// Default max acceleration, applied when no load-derived limit is tighter
+const ACCEL_DEF : f32 = 40.0;
+
+/// One `pin_step:pin_dir:max_rate:delta` axis definition, as given on the command line.
+struct AxisArg {
+    pin_step : u8,
+    pin_dir : u8,
+    max_rate : Velocity,
+    delta : Delta,
+}
+
+impl std::str::FromStr for AxisArg {
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        let parts : Vec<&str> = s.split(':').collect();
+        let [pin_step, pin_dir, max_rate, delta] = parts[..] else {
+            return Err(format!(
+                "expected 'pin_step:pin_dir:max_rate:delta', got '{}'", s
+            ));
+        };
+
+        Ok(Self {
+            pin_step: pin_step.parse().map_err(|_| format!("invalid step pin '{}'", pin_step))?,
+            pin_dir: pin_dir.parse().map_err(|_| format!("invalid direction pin '{}'", pin_dir))?,
+            max_rate: Velocity(max_rate.parse().map_err(|_| format!("invalid max_rate '{}'", max_rate))?),
+            delta: Delta(delta.parse().map_err(|_| format!("invalid delta '{}'", delta))?)
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+
+    info!("# SYRASP - stepper-coordinated");
+
+    let matches = command!()
+        .about("Drives several stepper motors in linear coordinated interpolation, so every \
+        axis given via '--axis pin_step:pin_dir:max_rate:delta' starts and finishes together")
+        .arg(arg!(--axis <AXIS> ... "An axis as 'pin_step:pin_dir:max_rate:delta'")
+            .value_parser(value_parser!(AxisArg)))
+        .arg(arg!([feedrate] "Commanded feedrate in rad/s, scaled down per-axis as needed (10 rad/s per default)")
+            .value_parser(value_parser!(f32)))
+        .arg(arg!(--accel <RAD_S2> "Maximum acceleration in rad/s^2, capped further by the applied load (ACCEL env var)")
+            .value_parser(value_parser!(f32)))
+        .arg(arg!(--jerk <RAD_S3> "Maximum jerk in rad/s^3; if given, the accel/decel ramps are eased instead of linear (JERK env var)")
+            .value_parser(value_parser!(f32)))
+        .get_matches();
+
+    let axis_args : Vec<&AxisArg> = matches.get_many("axis").expect("At least one --axis has to be given").collect();
+    let feedrate = Velocity(*matches.get_one("feedrate").unwrap_or(&10.0));
+    if feedrate.0 <= 0.0 {
+        return Err(format!("feedrate must be positive, got {}", feedrate.0).into());
+    }
+
+    let inertia = std::env::var("INERTIA").ok().map(|v| v.parse::<Inertia>().unwrap()).unwrap_or(Inertia::ZERO);
+    let force = std::env::var("FORCE").ok().map(|v| Force(v.parse::<f32>().unwrap())).unwrap_or(Force::ZERO);
+
+    let accel = matches.get_one::<f32>("accel").copied()
+        .or_else(|| std::env::var("ACCEL").ok().map(|v| v.parse().unwrap()))
+        .unwrap_or(ACCEL_DEF);
+    let jerk = matches.get_one::<f32>("jerk").copied()
+        .or_else(|| std::env::var("JERK").ok().map(|v| v.parse().unwrap()));
+    let accel = TrapezoidalProfile::accel_limit_for_load(accel, inertia, force);
+
+    let gpio = Gpio::new().unwrap();
+    info!("> Accessing GPIO done!");
+
+    let mut axes = Vec::with_capacity(axis_args.len());
+    let mut deltas = Vec::with_capacity(axis_args.len());
+
+    for axis_arg in &axis_args {
+        let mut stepper = Stepper::new(
+            GenericPWM::new(
+                gpio.get(axis_arg.pin_step).unwrap().into_output(),
+                gpio.get(axis_arg.pin_dir).unwrap().into_output()
+            )?,
+            StepperConst::MOT_17HE15_1504S
+        ).unwrap();
+
+        stepper.set_config(StepperConfig {
+            voltage: 12.0,
+            overload_current: None
+        });
+        stepper.setup()?;
+
+        stepper.apply_inertia(inertia);
+        stepper.apply_gen_force(force)?;
+
+        axes.push(Axis::new(stepper, axis_arg.max_rate));
+        deltas.push(axis_arg.delta);
+    }
+
+    debug!("> Driving {} axes at feedrate {}", axes.len(), feedrate);
+
+    info!("> Starting the coordinated movement ... ");
+    drive_coordinated(&mut axes, &deltas, feedrate, accel, jerk).await?;
+    info!("|  > Movement done!");
+
+    Ok(())
+}
@@ -1,79 +1,487 @@
 use core::f32::consts::PI;
+use std::fs::File;
+use std::io::{self, BufReader};
 
-use clap::{command, arg, value_parser};
-use log::{debug, info};
+use clap::{command, arg, value_parser, Command};
+use log::{debug, info, warn};
 use rppal::gpio::Gpio;
 use syact::prelude::*;
 
+#[path = "gcode.rs"]
+mod gcode;
+
+#[path = "a4988.rs"]
+mod a4988;
+
+#[path = "profile.rs"]
+mod profile;
+
+use a4988::A4988Driver;
+use profile::TrapezoidalProfile;
+
 // Define distance and max speed defaults
 const DELTA_DEF : Delta = Delta(2.0 * PI);
 const OMEGA_DEF : Velocity = Velocity(20.0);
 
+// Default max acceleration, applied when no load-derived limit is tighter
+const ACCEL_DEF : f32 = 40.0;
+
+// Default full steps per revolution of the `MOT_17HE15_1504S`, before microstepping
+const STEPS_PER_REV_DEF : f32 = 200.0;
+
 #[tokio::main]
-async fn main() -> Result<(), syact::Error> {
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Init logger
         env_logger::init();
 
         info!("# SYRASP - stepper-fixed_dist");
-    // 
+    //
 
     // Parse cmd args
-    let matches = command!() 
-        .about("Moves a stepper motor with a generic PWM controller connected to the pins 'pin_step' and 'pin_dir' by the given distance 
+    let matches = command!()
+        .about("Moves a stepper motor with a generic PWM controller connected to the pins 'pin_step' and 'pin_dir' by the given distance
         'delta' with the maximum speed 'omega', optionally enabling microstepping with the microstepcount 'micro'")
         .arg(arg!([pin_step] "Pin number of the step pin").value_parser(value_parser!(u8)))
         .arg(arg!([pin_dir] "Pin number of the direction pin").value_parser(value_parser!(u8)))
         .arg(arg!([delta] "Delta (distance) of the movement in rad (2pi [1 rev] per default)").value_parser(value_parser!(f32)))
         .arg(arg!([omega] "Velocity (velocity) of the movement in rad/s (10 rad/s per default)").value_parser(value_parser!(f32)))
+        .arg(arg!(--"pin-en" <PIN> "Enable pin of an A4988 driver (PIN_EN env var); switches from GenericPWM to a hardware-microstepping driver")
+            .value_parser(value_parser!(u8)))
+        .arg(arg!(--"pin-ms1" <PIN> "MS1 microstep-select pin of an A4988 driver (PIN_MS1 env var)").value_parser(value_parser!(u8)))
+        .arg(arg!(--"pin-ms2" <PIN> "MS2 microstep-select pin of an A4988 driver (PIN_MS2 env var)").value_parser(value_parser!(u8)))
+        .arg(arg!(--"pin-ms3" <PIN> "MS3 microstep-select pin of an A4988 driver (PIN_MS3 env var)").value_parser(value_parser!(u8)))
+        .arg(arg!(--hold <MODE> "Whether to keep the coils energized ('on') or de-energize them ('off') once the move completes")
+            .value_parser(["on", "off"])
+            .default_value("on"))
+        .arg(arg!(--accel <RAD_S2> "Maximum acceleration in rad/s^2, capped further by the applied load (ACCEL env var)")
+            .value_parser(value_parser!(f32)))
+        .arg(arg!(--jerk <RAD_S3> "Maximum jerk in rad/s^3; if given, the accel/decel ramps are eased instead of linear (JERK env var)")
+            .value_parser(value_parser!(f32)))
+        .subcommand(
+            Command::new("gcode")
+                .about("Streams G-code moves from a file (or stdin) to a stepper motor")
+                .arg(arg!(<pin_step> "Pin number of the step pin").value_parser(value_parser!(u8)))
+                .arg(arg!(<pin_dir> "Pin number of the direction pin").value_parser(value_parser!(u8)))
+                .arg(arg!([input] "Path to a G-code file (reads stdin if omitted)"))
+                .arg(arg!(--units <UNITS> "Units the X/A word is given in")
+                    .value_parser(value_parser!(gcode::Units))
+                    .default_value("rad"))
+                .arg(arg!(--"pin-step-y" <PIN> "Step pin of a second (Y) axis, enabling G2/G3 arc support")
+                    .value_parser(value_parser!(u8)))
+                .arg(arg!(--"pin-dir-y" <PIN> "Direction pin of a second (Y) axis")
+                    .value_parser(value_parser!(u8)))
+                .arg(arg!(--"mm-per-arc-segment" <LEN> "Maximum chord length an arc is subdivided into")
+                    .value_parser(value_parser!(f32))
+                    .default_value("0.1"))
+                .arg(arg!(--"arc-correction" <N> "Re-sync the incremental arc rotation with an exact trig call every N segments")
+                    .value_parser(value_parser!(usize))
+                    .default_value("20"))
+                .arg(arg!(--"pin-en" <PIN> "Enable pin of an A4988 driver (PIN_EN env var); switches from GenericPWM to a hardware-microstepping driver")
+                    .value_parser(value_parser!(u8)))
+                .arg(arg!(--"pin-ms1" <PIN> "MS1 microstep-select pin of an A4988 driver (PIN_MS1 env var)").value_parser(value_parser!(u8)))
+                .arg(arg!(--"pin-ms2" <PIN> "MS2 microstep-select pin of an A4988 driver (PIN_MS2 env var)").value_parser(value_parser!(u8)))
+                .arg(arg!(--"pin-ms3" <PIN> "MS3 microstep-select pin of an A4988 driver (PIN_MS3 env var)").value_parser(value_parser!(u8)))
+                .arg(arg!(--hold <MODE> "Whether to keep the coils energized ('on') or de-energize them ('off') once the stream completes")
+                    .value_parser(["on", "off"])
+                    .default_value("on"))
+                .arg(arg!(--accel <RAD_S2> "Maximum acceleration in rad/s^2, capped further by the applied load (ACCEL env var)")
+                    .value_parser(value_parser!(f32)))
+                .arg(arg!(--jerk <RAD_S3> "Maximum jerk in rad/s^3; if given, the accel/decel ramps are eased instead of linear (JERK env var)")
+                    .value_parser(value_parser!(f32)))
+        )
+        .subcommand(
+            Command::new("shell")
+                .about("Drops into an interactive prompt for jogging a stepper motor live")
+                .arg(arg!(<pin_step> "Pin number of the step pin").value_parser(value_parser!(u8)))
+                .arg(arg!(<pin_dir> "Pin number of the direction pin").value_parser(value_parser!(u8)))
+                .arg(arg!(--accel <RAD_S2> "Maximum acceleration in rad/s^2, capped further by the applied load (ACCEL env var)")
+                    .value_parser(value_parser!(f32)))
+                .arg(arg!(--jerk <RAD_S3> "Maximum jerk in rad/s^3; if given, the accel/decel ramps are eased instead of linear (JERK env var)")
+                    .value_parser(value_parser!(f32)))
+                .arg(arg!(--"pin-en" <PIN> "Enable pin of an A4988 driver (PIN_EN env var); switches from GenericPWM to a hardware-microstepping driver")
+                    .value_parser(value_parser!(u8)))
+                .arg(arg!(--"pin-ms1" <PIN> "MS1 microstep-select pin of an A4988 driver (PIN_MS1 env var)").value_parser(value_parser!(u8)))
+                .arg(arg!(--"pin-ms2" <PIN> "MS2 microstep-select pin of an A4988 driver (PIN_MS2 env var)").value_parser(value_parser!(u8)))
+                .arg(arg!(--"pin-ms3" <PIN> "MS3 microstep-select pin of an A4988 driver (PIN_MS3 env var)").value_parser(value_parser!(u8)))
+        )
         .get_matches();
 
+    if let Some(gcode_matches) = matches.subcommand_matches("gcode") {
+        return run_gcode(gcode_matches).await;
+    }
+
+    if let Some(shell_matches) = matches.subcommand_matches("shell") {
+        return run_shell(shell_matches).await;
+    }
+
     let pin_step : u8 = *matches.get_one("pin_step").expect("A valid step pin has to be provided");
     let pin_dir : u8 = *matches.get_one("pin_dir").expect("A valid direction pin has to be provided");
 
     let delta : Delta  = Delta(*matches.get_one("delta").unwrap_or(&DELTA_DEF.0));
     let omega : Velocity = Velocity(*matches.get_one("omega").unwrap_or(&OMEGA_DEF.0));
+    if omega.0 <= 0.0 {
+        return Err(format!("omega must be positive, got {}", omega.0).into());
+    }
+    let hold_off = matches.get_one::<String>("hold").map(String::as_str) == Some("off");
 
     // Load data
     let inertia = std::env::var("INERTIA").ok().map(|v| v.parse::<Inertia>().unwrap()).unwrap_or(Inertia::ZERO);
     let force = std::env::var("FORCE").ok().map(|v| Force(v.parse::<f32>().unwrap())).unwrap_or(Force::ZERO);
     let micro_opt = std::env::var("MICRO").ok().map(|v| v.parse::<MicroSteps>().unwrap());
 
+    let accel = matches.get_one::<f32>("accel").copied()
+        .or_else(|| std::env::var("ACCEL").ok().map(|v| v.parse().unwrap()))
+        .unwrap_or(ACCEL_DEF);
+    let jerk = matches.get_one::<f32>("jerk").copied()
+        .or_else(|| std::env::var("JERK").ok().map(|v| v.parse().unwrap()));
+
+    let pin_en = pin_arg(&matches, "pin-en", "PIN_EN");
+    let pin_ms1 = pin_arg(&matches, "pin-ms1", "PIN_MS1");
+    let pin_ms2 = pin_arg(&matches, "pin-ms2", "PIN_MS2");
+    let pin_ms3 = pin_arg(&matches, "pin-ms3", "PIN_MS3");
+
     info!("> Parsing data from env done!");
 
     let gpio = Gpio::new().unwrap();
     info!("> Accessing GPIO done!");
 
-    // Create the controls for a stepper motor
-    let mut stepper = Stepper::new(
-        GenericPWM::new(
-            gpio.get(pin_step).unwrap().into_output(), 
-            gpio.get(pin_dir).unwrap().into_output()
-        )?, 
-        StepperConst::MOT_17HE15_1504S
-    ).unwrap();
-
-    // Link the component to a system
-    stepper.set_config(StepperConfig { 
+    // Create the controls for a stepper motor, using a dedicated A4988 driver
+    // (with hardware microstepping and an enable line) if its pins were given
+    let (mut stepper, mut driver_aux) = build_stepper(
+        &gpio, pin_step, pin_dir, pin_en, pin_ms1, pin_ms2, pin_ms3, micro_opt, inertia, force
+    )?;
+
+    stepper.set_velocity_max(omega);
+
+    let accel = TrapezoidalProfile::accel_limit_for_load(accel, inertia, force);
+    debug!("> Data used: {{ Delta: {}, Omega: {}, Accel: {}, Jerk: {:?} }}", delta, omega, accel, jerk);
+
+    info!("> Starting the movement ... ");
+    let profile = TrapezoidalProfile::plan(delta, omega, accel, jerk);
+    for (segment, factor) in profile.segments() {
+        stepper.drive_rel(segment, factor).await?;
+    }
+    info!("|  > Movement done!");
+
+    if hold_off {
+        if let Some(aux) = driver_aux.as_mut() {
+            info!("> Releasing the coils ('--hold off') ... ");
+            aux.disable();
+        } else {
+            warn!("> '--hold off' has no effect without an A4988 enable pin; coils stay energized");
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a pin number from the CLI first, falling back to the given environment
+/// variable, as the `ACCEL`/`JERK` settings do.
+fn pin_arg(matches : &clap::ArgMatches, name : &str, env_key : &str) -> Option<u8> {
+    matches.get_one::<u8>(name).copied()
+        .or_else(|| std::env::var(env_key).ok().map(|v| v.parse().unwrap()))
+}
+
+/// Builds and configures a [`Stepper`] on `pin_step`/`pin_dir`, using a dedicated
+/// [`A4988Driver`] (with hardware microstepping and an enable line, left enabled)
+/// instead of a plain [`GenericPWM`] if all four of `pin_en`/`pin_ms1..3` were given.
+/// Shared by `main`, `run_gcode` and `run_shell` so the driver, microstepping and
+/// load setup stays in one place.
+fn build_stepper(
+    gpio : &Gpio,
+    pin_step : u8,
+    pin_dir : u8,
+    pin_en : Option<u8>,
+    pin_ms1 : Option<u8>,
+    pin_ms2 : Option<u8>,
+    pin_ms3 : Option<u8>,
+    micro_opt : Option<MicroSteps>,
+    inertia : Inertia,
+    force : Force
+) -> Result<(Stepper<GenericPWM>, Option<a4988::A4988Aux>), Box<dyn std::error::Error>> {
+    let (pwm, driver_aux) = match (pin_en, pin_ms1, pin_ms2, pin_ms3) {
+        (Some(pin_en), Some(pin_ms1), Some(pin_ms2), Some(pin_ms3)) => {
+            let driver = A4988Driver::new(gpio, pin_step, pin_dir, pin_en, [pin_ms1, pin_ms2, pin_ms3])?;
+            let (pwm, mut aux) = driver.into_parts(micro_opt.unwrap_or(MicroSteps(1)))?;
+            aux.enable();
+            (pwm, Some(aux))
+        },
+        (None, None, None, None) => (
+            GenericPWM::new(
+                gpio.get(pin_step).unwrap().into_output(),
+                gpio.get(pin_dir).unwrap().into_output()
+            )?,
+            None
+        ),
+        _ => {
+            warn!("> An A4988 driver needs --pin-en, --pin-ms1, --pin-ms2 and --pin-ms3 all set; \
+                only some were given, falling back to a plain GenericPWM with no enable/microstep-select control");
+            (
+                GenericPWM::new(
+                    gpio.get(pin_step).unwrap().into_output(),
+                    gpio.get(pin_dir).unwrap().into_output()
+                )?,
+                None
+            )
+        }
+    };
+
+    let mut stepper = Stepper::new(pwm, StepperConst::MOT_17HE15_1504S).unwrap();
+
+    stepper.set_config(StepperConfig {
         voltage: 12.0,    // System voltage in volts
         overload_current: None
-    }); 
+    });
     stepper.setup()?;
 
     if let Some(micro) = micro_opt {
         stepper.set_microsteps(micro);
     }
 
-    // Apply some loads
     stepper.apply_inertia(inertia);
     stepper.apply_gen_force(force)?;
 
-    stepper.set_velocity_max(omega);
+    Ok((stepper, driver_aux))
+}
 
-    debug!("> Data used: {{ Delta: {}, Omega: {} }}", delta, omega);
+/// Handles the `gcode` subcommand: sets up the stepper the same way `main` does, then
+/// streams moves from a file (or stdin) through [`gcode::run`], or through
+/// [`gcode::run_xy`] if a Y axis was given.
+async fn run_gcode(matches : &clap::ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let pin_step : u8 = *matches.get_one("pin_step").expect("A valid step pin has to be provided");
+    let pin_dir : u8 = *matches.get_one("pin_dir").expect("A valid direction pin has to be provided");
+    let input = matches.get_one::<String>("input");
+    let units = *matches.get_one::<gcode::Units>("units").unwrap_or(&gcode::Units::Rad);
+    let pin_step_y = matches.get_one::<u8>("pin-step-y").copied();
+    let pin_dir_y = matches.get_one::<u8>("pin-dir-y").copied();
+    let mm_per_arc_segment = *matches.get_one::<f32>("mm-per-arc-segment").unwrap_or(&0.1);
+    if mm_per_arc_segment <= 0.0 {
+        return Err(format!("--mm-per-arc-segment must be positive, got {}", mm_per_arc_segment).into());
+    }
+    let arc_correction = *matches.get_one::<usize>("arc-correction").unwrap_or(&20);
+    let hold_off = matches.get_one::<String>("hold").map(String::as_str) == Some("off");
 
-    info!("> Starting the movement ... ");
-    stepper.drive_rel(delta, Factor::MAX).await?;      
-    info!("|  > Movement done!");
+    // Load data
+    let inertia = std::env::var("INERTIA").ok().map(|v| v.parse::<Inertia>().unwrap()).unwrap_or(Inertia::ZERO);
+    let force = std::env::var("FORCE").ok().map(|v| Force(v.parse::<f32>().unwrap())).unwrap_or(Force::ZERO);
+    let micro_opt = std::env::var("MICRO").ok().map(|v| v.parse::<MicroSteps>().unwrap());
+    let steps_per_rev = std::env::var("STEPS_PER_REV").ok()
+        .map(|v| v.parse::<f32>().unwrap())
+        .unwrap_or(STEPS_PER_REV_DEF) * micro_opt.map(|m| m.0 as f32).unwrap_or(1.0);
+
+    let accel = matches.get_one::<f32>("accel").copied()
+        .or_else(|| std::env::var("ACCEL").ok().map(|v| v.parse().unwrap()))
+        .unwrap_or(ACCEL_DEF);
+    let jerk = matches.get_one::<f32>("jerk").copied()
+        .or_else(|| std::env::var("JERK").ok().map(|v| v.parse().unwrap()));
+    let accel = TrapezoidalProfile::accel_limit_for_load(accel, inertia, force);
+
+    let pin_en = pin_arg(matches, "pin-en", "PIN_EN");
+    let pin_ms1 = pin_arg(matches, "pin-ms1", "PIN_MS1");
+    let pin_ms2 = pin_arg(matches, "pin-ms2", "PIN_MS2");
+    let pin_ms3 = pin_arg(matches, "pin-ms3", "PIN_MS3");
+
+    info!("> Parsing data from env done!");
+
+    let gpio = Gpio::new().unwrap();
+    info!("> Accessing GPIO done!");
+
+    let (mut stepper, mut driver_aux) = build_stepper(
+        &gpio, pin_step, pin_dir, pin_en, pin_ms1, pin_ms2, pin_ms3, micro_opt, inertia, force
+    )?;
+
+    info!("> Starting to stream gcode ... ");
+
+    if pin_step_y.is_some() != pin_dir_y.is_some() {
+        warn!("> A Y axis needs both --pin-step-y and --pin-dir-y set; only one was given, \
+            falling back to a single-axis stream with no Y/arc support");
+    }
+
+    if let (Some(pin_step_y), Some(pin_dir_y)) = (pin_step_y, pin_dir_y) {
+        let mut stepper_y = Stepper::new(
+            GenericPWM::new(
+                gpio.get(pin_step_y).unwrap().into_output(),
+                gpio.get(pin_dir_y).unwrap().into_output()
+            )?,
+            StepperConst::MOT_17HE15_1504S
+        ).unwrap();
+
+        stepper_y.set_config(StepperConfig {
+            voltage: 12.0,
+            overload_current: None
+        });
+        stepper_y.setup()?;
+
+        if let Some(micro) = micro_opt {
+            stepper_y.set_microsteps(micro);
+        }
+
+        stepper_y.apply_inertia(inertia);
+        stepper_y.apply_gen_force(force)?;
+
+        let max_rate = Velocity(std::env::var("OMEGA_MAX").ok().map(|v| v.parse().unwrap()).unwrap_or(OMEGA_DEF.0));
+        let mut axes = [
+            gcode::motion::Axis::new(stepper, max_rate),
+            gcode::motion::Axis::new(stepper_y, max_rate)
+        ];
+
+        match input {
+            Some(path) => gcode::run_xy(&mut axes, BufReader::new(File::open(path)?), units, steps_per_rev, max_rate, mm_per_arc_segment, arc_correction, accel, jerk).await?,
+            None => gcode::run_xy(&mut axes, BufReader::new(io::stdin()), units, steps_per_rev, max_rate, mm_per_arc_segment, arc_correction, accel, jerk).await?
+        };
+    } else {
+        match input {
+            Some(path) => gcode::run(&mut stepper, BufReader::new(File::open(path)?), units, steps_per_rev, OMEGA_DEF, accel, jerk).await?,
+            None => gcode::run(&mut stepper, BufReader::new(io::stdin()), units, steps_per_rev, OMEGA_DEF, accel, jerk).await?
+        };
+    }
+
+    info!("|  > Stream done!");
+
+    if hold_off {
+        if let Some(aux) = driver_aux.as_mut() {
+            info!("> Releasing the coils ('--hold off') ... ");
+            aux.disable();
+        } else {
+            warn!("> '--hold off' has no effect without an A4988 enable pin; coils stay energized");
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the `shell` subcommand: sets up the stepper the same way `main` does, then
+/// drops into an interactive prompt accepting `move`/`speed`/`micro`/`enable`/
+/// `disable`/`stop`/`pos` lines on stdin.
+async fn run_shell(matches : &clap::ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    use tokio::io::AsyncBufReadExt;
+
+    let pin_step : u8 = *matches.get_one("pin_step").expect("A valid step pin has to be provided");
+    let pin_dir : u8 = *matches.get_one("pin_dir").expect("A valid direction pin has to be provided");
+
+    let inertia = std::env::var("INERTIA").ok().map(|v| v.parse::<Inertia>().unwrap()).unwrap_or(Inertia::ZERO);
+    let force = std::env::var("FORCE").ok().map(|v| Force(v.parse::<f32>().unwrap())).unwrap_or(Force::ZERO);
+
+    let accel = matches.get_one::<f32>("accel").copied()
+        .or_else(|| std::env::var("ACCEL").ok().map(|v| v.parse().unwrap()))
+        .unwrap_or(ACCEL_DEF);
+    let jerk = matches.get_one::<f32>("jerk").copied()
+        .or_else(|| std::env::var("JERK").ok().map(|v| v.parse().unwrap()));
+    let accel = TrapezoidalProfile::accel_limit_for_load(accel, inertia, force);
+
+    let micro_opt = std::env::var("MICRO").ok().map(|v| v.parse::<MicroSteps>().unwrap());
+    let pin_en = pin_arg(matches, "pin-en", "PIN_EN");
+    let pin_ms1 = pin_arg(matches, "pin-ms1", "PIN_MS1");
+    let pin_ms2 = pin_arg(matches, "pin-ms2", "PIN_MS2");
+    let pin_ms3 = pin_arg(matches, "pin-ms3", "PIN_MS3");
+
+    info!("> Parsing data from env done!");
+
+    let gpio = Gpio::new().unwrap();
+    info!("> Accessing GPIO done!");
+
+    let (mut stepper, mut driver_aux) = build_stepper(
+        &gpio, pin_step, pin_dir, pin_en, pin_ms1, pin_ms2, pin_ms3, micro_opt, inertia, force
+    )?;
+
+    stepper.set_velocity_max(OMEGA_DEF);
+
+    let mut position = Delta(0.0);
+    let mut omega = OMEGA_DEF;
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+
+    info!("> Entering the interactive shell ('help' for a list of commands, 'exit' to quit)");
+
+    while let Some(line) = lines.next_line().await? {
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("help") => println!(
+                "commands: move <delta> | speed <omega> | micro <n> | enable | disable | stop | pos | exit"
+            ),
+            Some("move") => {
+                let Some(delta) = words.next().and_then(|w| w.parse::<f32>().ok()) else {
+                    println!("usage: move <delta>");
+                    continue;
+                };
+                let delta = Delta(delta);
+
+                println!("> driving {} (type 'stop' + enter to interrupt)", delta);
+
+                let profile = TrapezoidalProfile::plan(delta, omega, accel, jerk);
+                let mut interrupted = false;
+
+                for (segment, factor) in profile.segments() {
+                    let wait_for_stop = async {
+                        loop {
+                            match lines.next_line().await {
+                                Ok(Some(l)) if l.trim() == "stop" => break,
+                                Ok(Some(_)) => continue,
+                                _ => break
+                            }
+                        }
+                    };
+
+                    tokio::select! {
+                        res = stepper.drive_rel(segment, factor) => {
+                            res?;
+                            position = Delta(position.0 + segment.0);
+                        },
+                        _ = wait_for_stop => {
+                            interrupted = true;
+                        }
+                    }
+
+                    if interrupted {
+                        break;
+                    }
+                }
+
+                println!("|  > movement {}", if interrupted { "interrupted" } else { "done" });
+            },
+            Some("speed") => {
+                let Some(new_omega) = words.next().and_then(|w| w.parse::<f32>().ok()) else {
+                    println!("usage: speed <omega>");
+                    continue;
+                };
+                if new_omega <= 0.0 {
+                    println!("omega must be positive, got {}", new_omega);
+                    continue;
+                }
+                omega = Velocity(new_omega);
+                stepper.set_velocity_max(omega);
+                println!("> max velocity set to {}", new_omega);
+            },
+            Some("micro") => {
+                let Some(micro) = words.next().and_then(|w| w.parse::<u32>().ok()) else {
+                    println!("usage: micro <n>");
+                    continue;
+                };
+                stepper.set_microsteps(MicroSteps(micro));
+                if let Some(aux) = driver_aux.as_mut() {
+                    aux.set_microsteps_hw(MicroSteps(micro));
+                }
+                println!("> microstep count set to {}", micro);
+            },
+            Some("enable") => {
+                match driver_aux.as_mut() {
+                    Some(aux) => { aux.enable(); println!("> coils enabled"); },
+                    None => println!("> this shell has no enable pin wired up; add an A4988 driver to (de-)energize the coils")
+                }
+            },
+            Some("disable") => {
+                match driver_aux.as_mut() {
+                    Some(aux) => { aux.disable(); println!("> coils disabled"); },
+                    None => println!("> this shell has no enable pin wired up; add an A4988 driver to (de-)energize the coils")
+                }
+            },
+            Some("stop") => println!("> nothing to stop"),
+            Some("pos") => println!("{}", position),
+            Some("exit") => break,
+            Some("") | None => continue,
+            Some(other) => println!("unknown command '{}', type 'help' for a list of commands", other)
+        }
+    }
 
     Ok(())
-}  
\ No newline at end of file
+}
@@ -0,0 +1,423 @@
+use core::f32::consts::PI;
+use std::io::BufRead;
+
+use clap::ValueEnum;
+use log::{debug, warn};
+use syact::prelude::*;
+
+#[path = "motion.rs"]
+pub mod motion;
+
+use motion::{Arc, Axis, Point2, TrapezoidalProfile, drive_coordinated};
+
+/// Units that the `X`/`A` word of a G-code line is given in.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Units {
+    Rad,
+    Deg,
+    Steps,
+}
+
+impl Units {
+    /// Converts a raw word value into an angle, given the number of (micro-)steps
+    /// that make up one full revolution.
+    fn to_delta(self, value : f32, steps_per_rev : f32) -> Delta {
+        match self {
+            Units::Rad => Delta(value),
+            Units::Deg => Delta(value * PI / 180.0),
+            Units::Steps => Delta(value * 2.0 * PI / steps_per_rev),
+        }
+    }
+
+    /// Converts a raw `F` word into a velocity, using the same per-unit conversion
+    /// factor as [`Units::to_delta`] so a feedrate means what the chosen `--units`
+    /// implies about `X`/`A`/`Y`/`I`/`J` on the same line.
+    fn to_velocity(self, value : f32, steps_per_rev : f32) -> Velocity {
+        Velocity(self.to_delta(value, steps_per_rev).0)
+    }
+}
+
+/// A parse error for a single G-code line, carrying the offending line number.
+#[derive(Debug)]
+pub struct GcodeError {
+    pub line : usize,
+    pub message : String,
+}
+
+impl std::fmt::Display for GcodeError {
+    fn fmt(&self, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "gcode error on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for GcodeError { }
+
+/// A single interpreted G-code line.
+#[derive(Debug)]
+pub enum GcodeLine {
+    /// `G0`/`G1`, optionally carrying a target (`X`/`A`), a second-axis target (`Y`)
+    /// and a feedrate (`F`).
+    Move { rapid : bool, target : Option<f32>, target_y : Option<f32>, feed : Option<f32> },
+    /// `G2`/`G3` - a circular arc to `(X, Y)` around a center offset by `(I, J)` from
+    /// the current position, clockwise for `G2` and counter-clockwise for `G3`.
+    Arc { clockwise : bool, target : Option<f32>, target_y : Option<f32>, i : Option<f32>, j : Option<f32>, feed : Option<f32> },
+    /// `G90` - absolute positioning.
+    SetAbsolute,
+    /// `G91` - relative positioning.
+    SetRelative,
+    /// Blank line / comment-only line.
+    Empty,
+}
+
+/// Strips `;...` and `(...)` comments plus a trailing `*NN` checksum from a raw line.
+fn strip_line(raw : &str) -> String {
+    let no_checksum = match raw.find('*') {
+        Some(idx) => &raw[.. idx],
+        None => raw,
+    };
+
+    let no_line_comment = match no_checksum.find(';') {
+        Some(idx) => &no_checksum[.. idx],
+        None => no_checksum,
+    };
+
+    let mut cleaned = String::with_capacity(no_line_comment.len());
+    let mut in_comment = false;
+    for c in no_line_comment.chars() {
+        match c {
+            '(' => in_comment = true,
+            ')' => in_comment = false,
+            _ if in_comment => { },
+            _ => cleaned.push(c)
+        }
+    }
+    cleaned
+}
+
+/// Parses a single line of G-code, returning the interpreted command. `modal_motion`
+/// is the last seen `G0`/`G1`/`G2`/`G3` word (0-3) and is both read and updated here,
+/// so a line with target/feed words but no G word of its own reuses it - real G-code
+/// senders routinely emit `G1 F1000 X10` once and then just `X20 Y5` on later lines.
+pub fn parse_line(raw : &str, line_no : usize, modal_motion : &mut Option<u32>) -> Result<GcodeLine, GcodeError> {
+    let cleaned = strip_line(raw);
+    let cleaned = cleaned.trim();
+
+    if cleaned.is_empty() {
+        return Ok(GcodeLine::Empty);
+    }
+
+    let mut g : Option<u32> = None;
+    let mut target : Option<f32> = None;
+    let mut target_y : Option<f32> = None;
+    let mut i_off : Option<f32> = None;
+    let mut j_off : Option<f32> = None;
+    let mut feed : Option<f32> = None;
+
+    for word in cleaned.split_whitespace() {
+        let mut chars = word.chars();
+        let letter = chars.next().ok_or_else(|| GcodeError {
+            line: line_no, message: "empty word".to_owned()
+        })?;
+        let value = chars.as_str();
+
+        match letter.to_ascii_uppercase() {
+            'G' => g = Some(value.parse().map_err(|_| GcodeError {
+                line: line_no, message: format!("invalid G word '{}'", word)
+            })?),
+            'X' | 'A' => target = Some(value.parse().map_err(|_| GcodeError {
+                line: line_no, message: format!("invalid {} word '{}'", letter, word)
+            })?),
+            'Y' => target_y = Some(value.parse().map_err(|_| GcodeError {
+                line: line_no, message: format!("invalid Y word '{}'", word)
+            })?),
+            'I' => i_off = Some(value.parse().map_err(|_| GcodeError {
+                line: line_no, message: format!("invalid I word '{}'", word)
+            })?),
+            'J' => j_off = Some(value.parse().map_err(|_| GcodeError {
+                line: line_no, message: format!("invalid J word '{}'", word)
+            })?),
+            'F' => feed = Some(value.parse().map_err(|_| GcodeError {
+                line: line_no, message: format!("invalid F word '{}'", word)
+            })?),
+            'N' => { }, // Line number word, not relevant here
+            _ => return Err(GcodeError {
+                line: line_no, message: format!("unsupported word '{}'", word)
+            })
+        }
+    }
+
+    let has_motion_word = target.is_some() || target_y.is_some() || feed.is_some() || i_off.is_some() || j_off.is_some();
+    let g = g.or_else(|| if has_motion_word { *modal_motion } else { None });
+
+    match g {
+        Some(0) => { *modal_motion = g; Ok(GcodeLine::Move { rapid: true, target, target_y, feed }) },
+        Some(1) => { *modal_motion = g; Ok(GcodeLine::Move { rapid: false, target, target_y, feed }) },
+        Some(2) => { *modal_motion = g; Ok(GcodeLine::Arc { clockwise: true, target, target_y, i: i_off, j: j_off, feed }) },
+        Some(3) => { *modal_motion = g; Ok(GcodeLine::Arc { clockwise: false, target, target_y, i: i_off, j: j_off, feed }) },
+        Some(90) => Ok(GcodeLine::SetAbsolute),
+        Some(91) => Ok(GcodeLine::SetRelative),
+        Some(other) => Err(GcodeError {
+            line: line_no, message: format!("unsupported command G{}", other)
+        }),
+        None if has_motion_word => Err(GcodeError {
+            line: line_no, message: "word given without a preceding G command".to_owned()
+        }),
+        None => Ok(GcodeLine::Empty)
+    }
+}
+
+/// Streams G-code from `reader` and drives `stepper` accordingly, tracking an
+/// absolute/relative position accumulator across `G90`/`G91` mode switches and
+/// ramping every move through an `accel_max`/`jerk_max`-limited
+/// [`TrapezoidalProfile`] instead of jumping straight to `feedrate`.
+pub async fn run<C>(
+    stepper : &mut Stepper<C>,
+    reader : impl BufRead,
+    units : Units,
+    steps_per_rev : f32,
+    feedrate : Velocity,
+    accel_max : f32,
+    jerk_max : Option<f32>
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut absolute = true;
+    let mut position = Delta(0.0);
+    let mut feedrate = feedrate;
+    let mut modal_motion = None;
+
+    stepper.set_velocity_max(feedrate);
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line?;
+
+        match parse_line(&line, line_no, &mut modal_motion)? {
+            GcodeLine::Empty => continue,
+            GcodeLine::SetAbsolute => absolute = true,
+            GcodeLine::SetRelative => absolute = false,
+            GcodeLine::Move { rapid: _, target, target_y, feed } => {
+                if target_y.is_some() {
+                    warn!("> Line {}: Y word given on a single-axis stream, ignoring", line_no);
+                }
+
+                if let Some(feed) = feed {
+                    feedrate = units.to_velocity(feed, steps_per_rev);
+                    stepper.set_velocity_max(feedrate);
+                }
+
+                let Some(target) = target else {
+                    warn!("> Line {}: move without a X/A word, ignoring", line_no);
+                    continue;
+                };
+
+                let target = units.to_delta(target, steps_per_rev);
+                let rel = if absolute { Delta(target.0 - position.0) } else { target };
+
+                debug!("> Line {}: driving {}", line_no, rel);
+
+                let profile = TrapezoidalProfile::plan(rel, feedrate, accel_max, jerk_max);
+                for (segment, factor) in profile.segments() {
+                    stepper.drive_rel(segment, factor).await?;
+                }
+
+                position = if absolute { target } else { Delta(position.0 + target.0) };
+            },
+            GcodeLine::Arc { .. } => return Err(Box::new(GcodeError {
+                line: line_no, message: "G2/G3 requires a two-axis stream (see `run_xy`)".to_owned()
+            }))
+        }
+    }
+
+    Ok(())
+}
+
+/// Streams G-code from `reader` and drives `axes` (`[x, y]`) accordingly, supporting
+/// `G0`/`G1` two-axis linear moves and `G2`/`G3` circular arcs expanded into chords
+/// via [`Arc::segments`]. Every move, including each arc chord, is ramped through
+/// `accel_max`/`jerk_max` by [`drive_coordinated`].
+pub async fn run_xy<C>(
+    axes : &mut [Axis<C>; 2],
+    reader : impl BufRead,
+    units : Units,
+    steps_per_rev : f32,
+    feedrate : Velocity,
+    mm_per_arc_segment : f32,
+    arc_correction : usize,
+    accel_max : f32,
+    jerk_max : Option<f32>
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut absolute = true;
+    let mut position = Point2 { x: 0.0, y: 0.0 };
+    let mut feedrate = feedrate;
+    let mut modal_motion = None;
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line?;
+
+        match parse_line(&line, line_no, &mut modal_motion)? {
+            GcodeLine::Empty => continue,
+            GcodeLine::SetAbsolute => absolute = true,
+            GcodeLine::SetRelative => absolute = false,
+            GcodeLine::Move { rapid: _, target, target_y, feed } => {
+                if let Some(feed) = feed {
+                    feedrate = units.to_velocity(feed, steps_per_rev);
+                }
+
+                let target_point = resolve_target(position, target, target_y, absolute, units, steps_per_rev);
+
+                debug!("> Line {}: driving to {:?}", line_no, target_point);
+                let deltas = [
+                    Delta(target_point.x - position.x),
+                    Delta(target_point.y - position.y)
+                ];
+                drive_coordinated(axes, &deltas, feedrate, accel_max, jerk_max).await?;
+                position = target_point;
+            },
+            GcodeLine::Arc { clockwise, target, target_y, i, j, feed } => {
+                if let Some(feed) = feed {
+                    feedrate = units.to_velocity(feed, steps_per_rev);
+                }
+
+                let end = resolve_target(position, target, target_y, absolute, units, steps_per_rev);
+                let center = Point2 {
+                    x: position.x + units.to_delta(i.unwrap_or(0.0), steps_per_rev).0,
+                    y: position.y + units.to_delta(j.unwrap_or(0.0), steps_per_rev).0
+                };
+
+                let arc = Arc { start: position, end, center, clockwise };
+                let segments = arc.segments(mm_per_arc_segment, arc_correction.max(1));
+
+                debug!("> Line {}: arc expanded into {} segments", line_no, segments.len() - 1);
+
+                for segment_end in segments.into_iter().skip(1) {
+                    let deltas = [
+                        Delta(segment_end.x - position.x),
+                        Delta(segment_end.y - position.y)
+                    ];
+                    drive_coordinated(axes, &deltas, feedrate, accel_max, jerk_max).await?;
+                    position = segment_end;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves an `X`/`Y` target word pair against the running `position`, honoring the
+/// current `G90`/`G91` absolute/relative mode.
+fn resolve_target(
+    position : Point2,
+    target : Option<f32>,
+    target_y : Option<f32>,
+    absolute : bool,
+    units : Units,
+    steps_per_rev : f32
+) -> Point2 {
+    let x = target.map(|v| units.to_delta(v, steps_per_rev).0);
+    let y = target_y.map(|v| units.to_delta(v, steps_per_rev).0);
+
+    if absolute {
+        Point2 { x: x.unwrap_or(position.x), y: y.unwrap_or(position.y) }
+    } else {
+        Point2 { x: position.x + x.unwrap_or(0.0), y: position.y + y.unwrap_or(0.0) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_line_removes_line_comments_and_checksum() {
+        assert_eq!(strip_line("G1 X1 ; move right"), "G1 X1 ");
+        assert_eq!(strip_line("G1 X1*42"), "G1 X1");
+        assert_eq!(strip_line("G1 X1 ; comment*not-a-checksum"), "G1 X1 ");
+    }
+
+    #[test]
+    fn strip_line_removes_inline_parenthesized_comments() {
+        assert_eq!(strip_line("G1 (rapid) X1 (to the right) Y2"), "G1  X1  Y2");
+    }
+
+    #[test]
+    fn parse_line_skips_blank_and_comment_only_lines() {
+        assert!(matches!(parse_line("", 1, &mut None).unwrap(), GcodeLine::Empty));
+        assert!(matches!(parse_line("   ", 2, &mut None).unwrap(), GcodeLine::Empty));
+        assert!(matches!(parse_line("; just a comment", 3, &mut None).unwrap(), GcodeLine::Empty));
+    }
+
+    #[test]
+    fn parse_line_reads_move_words() {
+        match parse_line("G1 X1.5 F20", 1, &mut None).unwrap() {
+            GcodeLine::Move { rapid, target, target_y, feed } => {
+                assert!(!rapid);
+                assert_eq!(target, Some(1.5));
+                assert_eq!(target_y, None);
+                assert_eq!(feed, Some(20.0));
+            },
+            other => panic!("expected a Move, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn parse_line_reads_arc_words() {
+        match parse_line("G2 X1 Y2 I0.5 J0", 1, &mut None).unwrap() {
+            GcodeLine::Arc { clockwise, target, target_y, i, j, .. } => {
+                assert!(clockwise);
+                assert_eq!(target, Some(1.0));
+                assert_eq!(target_y, Some(2.0));
+                assert_eq!(i, Some(0.5));
+                assert_eq!(j, Some(0.0));
+            },
+            other => panic!("expected an Arc, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn parse_line_reads_positioning_mode_words() {
+        assert!(matches!(parse_line("G90", 1, &mut None).unwrap(), GcodeLine::SetAbsolute));
+        assert!(matches!(parse_line("G91", 1, &mut None).unwrap(), GcodeLine::SetRelative));
+    }
+
+    #[test]
+    fn parse_line_reports_the_offending_line_number_on_bad_words() {
+        let err = parse_line("G1 X1.5.5", 7, &mut None).unwrap_err();
+        assert_eq!(err.line, 7);
+
+        let err = parse_line("G99", 8, &mut None).unwrap_err();
+        assert_eq!(err.line, 8);
+
+        let err = parse_line("X1", 9, &mut None).unwrap_err();
+        assert_eq!(err.line, 9);
+    }
+
+    #[test]
+    fn parse_line_reuses_the_last_motion_mode() {
+        let mut modal_motion = None;
+
+        match parse_line("G1 F1000 X10", 1, &mut modal_motion).unwrap() {
+            GcodeLine::Move { rapid, .. } => assert!(!rapid),
+            other => panic!("expected a Move, got {:?}", other)
+        }
+
+        match parse_line("X20 Y5", 2, &mut modal_motion).unwrap() {
+            GcodeLine::Move { target, target_y, .. } => {
+                assert_eq!(target, Some(20.0));
+                assert_eq!(target_y, Some(5.0));
+            },
+            other => panic!("expected a Move, got {:?}", other)
+        }
+
+        match parse_line("X30", 3, &mut modal_motion).unwrap() {
+            GcodeLine::Move { target, .. } => assert_eq!(target, Some(30.0)),
+            other => panic!("expected a Move, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn parse_line_rejects_words_with_no_motion_mode_ever_set() {
+        let err = parse_line("X1", 1, &mut None).unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+}
@@ -0,0 +1,266 @@
+use core::f32::consts::PI;
+
+use syact::prelude::*;
+
+#[path = "profile.rs"]
+pub mod profile;
+
+pub use profile::TrapezoidalProfile;
+
+/// A single axis taking part in a coordinated move: the stepper to drive and the
+/// maximum velocity it must never be commanded past, regardless of the requested
+/// feedrate.
+pub struct Axis<C> {
+    pub stepper : Stepper<C>,
+    pub max_rate : Velocity,
+}
+
+impl<C> Axis<C> {
+    pub fn new(stepper : Stepper<C>, max_rate : Velocity) -> Self {
+        Self { stepper, max_rate }
+    }
+}
+
+/// Drives every axis in `axes` by its corresponding entry in `deltas` so all axes
+/// start and finish together, scaling `feedrate` down to the slowest axis and
+/// ramping through one shared `accel_max`/`jerk_max`-limited [`TrapezoidalProfile`]
+/// planned on the dominant (slowest) axis and replayed as a distance fraction on
+/// the rest.
+pub async fn drive_coordinated<C>(
+    axes : &mut [Axis<C>],
+    deltas : &[Delta],
+    feedrate : Velocity,
+    accel_max : f32,
+    jerk_max : Option<f32>
+) -> Result<(), syact::Error> {
+    assert_eq!(axes.len(), deltas.len(), "an axis velocity limit must be given for every delta");
+
+    let max_rates : Vec<Velocity> = axes.iter().map(|axis| axis.max_rate).collect();
+    let Some((dominant, velocities)) = coordinated_velocities(deltas, &max_rates, feedrate) else {
+        return Ok(());
+    };
+
+    for (axis, velocity) in axes.iter_mut().zip(velocities.iter()) {
+        axis.stepper.set_velocity_max(*velocity);
+    }
+
+    let dominant_distance = deltas[dominant].0.abs();
+    let profile = TrapezoidalProfile::plan(Delta(dominant_distance), velocities[dominant], accel_max, jerk_max);
+
+    for (seg_delta, factor) in profile.segments() {
+        let fraction = seg_delta.0 / dominant_distance;
+
+        let futures = axes.iter_mut().zip(deltas.iter())
+            .map(|(axis, delta)| axis.stepper.drive_rel(Delta(delta.0 * fraction), factor));
+
+        futures::future::try_join_all(futures).await?;
+    }
+
+    Ok(())
+}
+
+/// The dominant-axis scaling math behind [`drive_coordinated`], as a pure function
+/// of deltas/rates/feedrate. Returns the dominant axis index and the per-axis
+/// velocity needed for every axis to finish together, or `None` if every delta is zero.
+fn coordinated_velocities(deltas : &[Delta], max_rates : &[Velocity], feedrate : Velocity) -> Option<(usize, Vec<Velocity>)> {
+    let mut t = 0.0_f32;
+    let mut dominant = 0;
+
+    for (idx, (max_rate, delta)) in max_rates.iter().zip(deltas.iter()).enumerate() {
+        let axis_t = (delta.0.abs() / max_rate.0.min(feedrate.0)).abs();
+        if axis_t > t {
+            t = axis_t;
+            dominant = idx;
+        }
+    }
+
+    if t <= 0.0 {
+        return None;
+    }
+
+    let velocities = deltas.iter().map(|delta| Velocity(delta.0.abs() / t)).collect();
+    Some((dominant, velocities))
+}
+
+/// A point in the plane of two coordinated axes.
+#[derive(Clone, Copy, Debug)]
+pub struct Point2 {
+    pub x : f32,
+    pub y : f32,
+}
+
+/// A circular arc between `start` and `end`, as described by `G2`/`G3` with an `I`/`J`
+/// center offset relative to `start`.
+pub struct Arc {
+    pub start : Point2,
+    pub end : Point2,
+    pub center : Point2,
+    pub clockwise : bool,
+}
+
+impl Arc {
+    /// Subdivides the arc into chord segments no longer than `mm_per_segment`, rotating
+    /// the radius vector incrementally and re-syncing with an exact `sin`/`cos` call
+    /// every `arc_correction` segments to cancel drift. Handles full circles (`start == end`).
+    pub fn segments(&self, mm_per_segment : f32, arc_correction : usize) -> Vec<Point2> {
+        let mm_per_segment = mm_per_segment.max(f32::EPSILON);
+        let arc_correction = arc_correction.max(1);
+
+        let r0 = Point2 { x: self.start.x - self.center.x, y: self.start.y - self.center.y };
+        let r1 = Point2 { x: self.end.x - self.center.x, y: self.end.y - self.center.y };
+        let radius = (r0.x * r0.x + r0.y * r0.y).sqrt();
+
+        let theta_start = r0.y.atan2(r0.x);
+        let mut theta_end = r1.y.atan2(r1.x);
+
+        // Normalizing the end angle into the sweep direction also naturally produces a
+        // full 2*PI sweep for a full circle, since start == end makes theta_end equal
+        // theta_start before normalization.
+        if self.clockwise {
+            while theta_end >= theta_start { theta_end -= 2.0 * PI; }
+        } else {
+            while theta_end <= theta_start { theta_end += 2.0 * PI; }
+        }
+
+        let sweep = theta_end - theta_start;
+        let arc_len = radius * sweep.abs();
+        let segment_count = ((arc_len / mm_per_segment).ceil() as usize).max(1);
+        let theta_per_segment = sweep / segment_count as f32;
+
+        let cos_t = theta_per_segment.cos();
+        let sin_t = theta_per_segment.sin();
+
+        let mut x = r0.x;
+        let mut y = r0.y;
+
+        let mut points = Vec::with_capacity(segment_count + 1);
+        points.push(self.start);
+
+        for i in 1 ..= segment_count {
+            if i % arc_correction == 0 {
+                let theta = theta_start + theta_per_segment * i as f32;
+                x = radius * theta.cos();
+                y = radius * theta.sin();
+            } else {
+                let (nx, ny) = (x * cos_t - y * sin_t, x * sin_t + y * cos_t);
+                x = nx;
+                y = ny;
+            }
+
+            points.push(Point2 { x: self.center.x + x, y: self.center.y + y });
+        }
+
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinated_velocities_scales_down_to_the_slower_axis() {
+        // X needs to cover twice the distance of Y at the same max_rate - X is
+        // dominant, and Y must be driven at half its own max_rate to finish with it.
+        let deltas = [Delta(4.0), Delta(2.0)];
+        let max_rates = [Velocity(2.0), Velocity(2.0)];
+
+        let (dominant, velocities) = coordinated_velocities(&deltas, &max_rates, Velocity(2.0)).unwrap();
+
+        assert_eq!(dominant, 0);
+        assert_eq!(velocities[0].0, 2.0);
+        assert_eq!(velocities[1].0, 1.0);
+    }
+
+    #[test]
+    fn coordinated_velocities_caps_at_the_commanded_feedrate() {
+        // Both axes could go at max_rate 10, but the feedrate caps the move time.
+        let deltas = [Delta(5.0), Delta(5.0)];
+        let max_rates = [Velocity(10.0), Velocity(10.0)];
+
+        let (_, velocities) = coordinated_velocities(&deltas, &max_rates, Velocity(1.0)).unwrap();
+
+        assert_eq!(velocities[0].0, 1.0);
+        assert_eq!(velocities[1].0, 1.0);
+    }
+
+    #[test]
+    fn coordinated_velocities_returns_none_when_every_delta_is_zero() {
+        let deltas = [Delta(0.0), Delta(0.0)];
+        let max_rates = [Velocity(2.0), Velocity(2.0)];
+
+        assert!(coordinated_velocities(&deltas, &max_rates, Velocity(2.0)).is_none());
+    }
+
+    #[test]
+    fn arc_segments_starts_and_ends_at_the_given_points() {
+        // Quarter circle, counter-clockwise, centered on the origin.
+        let arc = Arc {
+            start: Point2 { x: 1.0, y: 0.0 },
+            end: Point2 { x: 0.0, y: 1.0 },
+            center: Point2 { x: 0.0, y: 0.0 },
+            clockwise: false,
+        };
+
+        let points = arc.segments(0.05, 20);
+
+        assert_eq!(points.first().unwrap().x, 1.0);
+        assert_eq!(points.first().unwrap().y, 0.0);
+
+        let last = points.last().unwrap();
+        assert!((last.x - 0.0).abs() < 1e-4, "x = {}", last.x);
+        assert!((last.y - 1.0).abs() < 1e-4, "y = {}", last.y);
+
+        // Every intermediate point stays on the unit circle, i.e. the incremental
+        // rotation (plus periodic re-sync) doesn't drift the radius.
+        for p in &points {
+            let r = (p.x * p.x + p.y * p.y).sqrt();
+            assert!((r - 1.0).abs() < 1e-3, "radius drifted to {}", r);
+        }
+    }
+
+    #[test]
+    fn arc_segments_honors_clockwise_vs_counter_clockwise() {
+        let start = Point2 { x: 1.0, y: 0.0 };
+        let end = Point2 { x: 0.0, y: 1.0 };
+        let center = Point2 { x: 0.0, y: 0.0 };
+
+        let ccw = Arc { start, end, center, clockwise: false }.segments(0.05, 20);
+        let cw = Arc { start, end, center, clockwise: true }.segments(0.05, 20);
+
+        // The short way (CCW, a quarter turn) must use far fewer segments than the
+        // long way around (CW, three quarters of a turn) for the same chord length.
+        assert!(ccw.len() < cw.len(), "ccw={} cw={}", ccw.len(), cw.len());
+    }
+
+    #[test]
+    fn arc_segments_handles_a_full_circle() {
+        let arc = Arc {
+            start: Point2 { x: 1.0, y: 0.0 },
+            end: Point2 { x: 1.0, y: 0.0 },
+            center: Point2 { x: 0.0, y: 0.0 },
+            clockwise: false,
+        };
+
+        let points = arc.segments(0.1, 20);
+
+        assert!(points.len() > 2, "a full circle should be subdivided into several segments");
+        let last = points.last().unwrap();
+        assert!((last.x - 1.0).abs() < 1e-3 && (last.y - 0.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn arc_segments_clamps_non_positive_mm_per_segment_and_arc_correction() {
+        let arc = Arc {
+            start: Point2 { x: 1.0, y: 0.0 },
+            end: Point2 { x: 0.0, y: 1.0 },
+            center: Point2 { x: 0.0, y: 0.0 },
+            clockwise: false,
+        };
+
+        // Should fall back to a safe minimum instead of dividing by zero / looping forever.
+        let points = arc.segments(0.0, 0);
+        assert!(points.len() >= 2);
+        assert!(points.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+    }
+}
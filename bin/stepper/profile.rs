@@ -0,0 +1,215 @@
+use syact::prelude::*;
+
+/// Segments per phase (accel/cruise/decel) of a move: the whole move is cut into
+/// this many times its number of phases equal time slices, so a longer phase gets
+/// more of them than a shorter one. Each segment becomes one `drive_rel` call at a
+/// constant `Factor`.
+const SEGMENTS_PER_PHASE : usize = 16;
+
+/// A quintic "smootherstep" easing curve - used as the ramp's velocity shape, it has
+/// zero acceleration *and* jerk at both endpoints - used to approximate a jerk-limited ramp without a full 7-phase
+/// S-curve planner.
+fn smootherstep(x : f32) -> f32 {
+    x * x * x * (x * (x * 6.0 - 15.0) + 10.0)
+}
+
+/// Antiderivative of [`smootherstep`] on `[0, 1]`: the fraction of `v_peak * t_ramp`
+/// covered by normalized time `x`.
+fn smootherstep_integral(x : f32) -> f32 {
+    x.powi(6) - 3.0 * x.powi(5) + 2.5 * x.powi(4)
+}
+
+/// A trapezoidal (or triangular, for moves too short to reach cruise speed) velocity
+/// profile for a single move: accelerate at `accel_max` up to `omega_max`, cruise,
+/// then decelerate back to zero. With `jerk_max` set, the ramps are eased via
+/// [`smootherstep`] and lengthened instead of driven linearly.
+pub struct TrapezoidalProfile {
+    delta : Delta,
+    omega_max : Velocity,
+    accel_max : f32,
+    jerk_max : Option<f32>,
+}
+
+impl TrapezoidalProfile {
+    /// Plans a profile for covering `delta` without exceeding `omega_max` or
+    /// `accel_max`. `accel_max` is expected to already reflect the applied load
+    /// (see [`TrapezoidalProfile::accel_limit_for_load`]).
+    pub fn plan(delta : Delta, omega_max : Velocity, accel_max : f32, jerk_max : Option<f32>) -> Self {
+        Self {
+            delta,
+            omega_max: Velocity(omega_max.0.max(f32::EPSILON)),
+            accel_max: accel_max.max(f32::EPSILON),
+            jerk_max
+        }
+    }
+
+    /// Caps a requested acceleration limit to what the load can actually deliver:
+    /// `force / inertia`, the angular acceleration the applied torque produces
+    /// against the applied inertia. Loads of zero impose no extra cap.
+    pub fn accel_limit_for_load(requested : f32, inertia : Inertia, force : Force) -> f32 {
+        if inertia.0 > 0.0 && force.0 > 0.0 {
+            requested.min(force.0 / inertia.0)
+        } else {
+            requested
+        }
+    }
+
+    /// The move split into `(Delta, Factor)` segments - short relative moves and the
+    /// `Factor` (of `omega_max`) to drive each one at - ramping up, cruising, then
+    /// ramping down.
+    pub fn segments(&self) -> Vec<(Delta, Factor)> {
+        let distance = self.delta.0.abs();
+        let sign = self.delta.0.signum();
+
+        if distance <= 0.0 {
+            return Vec::new();
+        }
+
+        // Distance needed to accelerate from 0 to omega_max and back down again
+        let accel_distance = self.omega_max.0 * self.omega_max.0 / self.accel_max;
+
+        let (mut t_ramp, mut t_cruise, mut omega_peak) = if accel_distance >= distance {
+            // Triangular profile: the move is too short to reach omega_max
+            let omega_peak = (distance * self.accel_max).sqrt();
+            (omega_peak / self.accel_max, 0.0, omega_peak)
+        } else {
+            let cruise_distance = distance - accel_distance;
+            (self.omega_max.0 / self.accel_max, cruise_distance / self.omega_max.0, self.omega_max.0)
+        };
+
+        // An eased ramp peaks at 1.875x the linear ramp's acceleration, and a jerk limit
+        // may need a longer ramp still; lengthen the ramp for both, then re-derive the
+        // split so the longer ramp still fits `distance`, shrinking the cruise phase
+        // first and, if that's not enough, `omega_peak` itself
+        if let Some(jerk_max) = self.jerk_max {
+            let t_ramp_floor = (2.0 * (omega_peak / jerk_max.max(f32::EPSILON)).sqrt()).max(1.875 * t_ramp);
+
+            if t_ramp_floor > t_ramp {
+                t_ramp = t_ramp_floor;
+                let ramp_distance = omega_peak * t_ramp; // accel + decel, each averaging omega_peak / 2
+
+                if ramp_distance >= distance {
+                    omega_peak = distance / t_ramp;
+                    t_cruise = 0.0;
+                } else {
+                    t_cruise = (distance - ramp_distance) / omega_peak;
+                }
+            }
+        }
+
+        if t_ramp <= 0.0 && t_cruise <= 0.0 {
+            return Vec::new();
+        }
+
+        let eased = self.jerk_max.is_some();
+        let ramp_position = |t : f32| -> f32 {
+            if t_ramp <= 0.0 {
+                return 0.0;
+            }
+            let x = (t / t_ramp).clamp(0.0, 1.0);
+            omega_peak * t_ramp * if eased { smootherstep_integral(x) } else { 0.5 * x * x }
+        };
+
+        let cruise_start = ramp_position(t_ramp);
+        let t_total = 2.0 * t_ramp + t_cruise;
+
+        let position_at = |t : f32| -> f32 {
+            if t <= t_ramp {
+                ramp_position(t)
+            } else if t <= t_ramp + t_cruise {
+                cruise_start + omega_peak * (t - t_ramp)
+            } else {
+                let t_decel = (t - t_ramp - t_cruise).clamp(0.0, t_ramp);
+                distance - ramp_position(t_ramp - t_decel)
+            }
+        };
+
+        let segment_count = SEGMENTS_PER_PHASE * if t_cruise > 0.0 { 3 } else { 2 };
+        let dt = t_total / segment_count as f32;
+
+        let mut segments = Vec::with_capacity(segment_count);
+        let mut travelled = 0.0_f32;
+
+        for i in 0 .. segment_count {
+            let t0 = i as f32 * dt;
+            let t1 = (t0 + dt).min(t_total);
+
+            let seg_distance = (position_at(t1) - position_at(t0)).max(0.0);
+            let omega_mid = seg_distance / dt;
+            let factor = (omega_mid / self.omega_max.0).clamp(0.0, 1.0);
+
+            segments.push((Delta(sign * seg_distance), Factor(factor)));
+            travelled += seg_distance;
+        }
+
+        // Fold any rounding drift into the last segment so the total matches `delta` exactly
+        if let Some(last) = segments.last_mut() {
+            last.0 = Delta(last.0.0 + sign * (distance - travelled));
+        }
+
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sums up the `Delta`s of every segment, signed, to compare against the
+    /// originally requested move distance.
+    fn total_delta(segments : &[(Delta, Factor)]) -> f32 {
+        segments.iter().map(|(delta, _)| delta.0).sum()
+    }
+
+    #[test]
+    fn trapezoidal_profile_reaches_cruise_speed_on_a_long_move() {
+        let profile = TrapezoidalProfile::plan(Delta(100.0), Velocity(10.0), 5.0, None);
+        let segments = profile.segments();
+
+        assert!(!segments.is_empty());
+        assert!((total_delta(&segments) - 100.0).abs() < 1e-3);
+
+        // A long enough move reaches the full commanded factor somewhere in the middle.
+        assert!(segments.iter().any(|(_, factor)| (factor.0 - 1.0).abs() < 1e-3));
+    }
+
+    #[test]
+    fn triangular_profile_never_reaches_cruise_speed_on_a_short_move() {
+        // Too short to ever hit omega_max = 10 at accel_max = 5.
+        let profile = TrapezoidalProfile::plan(Delta(1.0), Velocity(10.0), 5.0, None);
+        let segments = profile.segments();
+
+        assert!(!segments.is_empty());
+        assert!((total_delta(&segments) - 1.0).abs() < 1e-3);
+        assert!(segments.iter().all(|(_, factor)| factor.0 < 1.0));
+    }
+
+    #[test]
+    fn jerk_limited_profile_still_covers_the_full_distance() {
+        let profile = TrapezoidalProfile::plan(Delta(50.0), Velocity(10.0), 5.0, Some(2.0));
+        let segments = profile.segments();
+
+        assert!(!segments.is_empty());
+        assert!((total_delta(&segments) - 50.0).abs() < 1e-3);
+        assert!(segments.iter().all(|(_, factor)| factor.0.is_finite() && factor.0 >= 0.0));
+    }
+
+    #[test]
+    fn segments_are_empty_for_a_zero_distance_move() {
+        let profile = TrapezoidalProfile::plan(Delta(0.0), Velocity(10.0), 5.0, None);
+        assert!(profile.segments().is_empty());
+    }
+
+    #[test]
+    fn zero_omega_max_does_not_produce_nan_segments() {
+        // `plan` must clamp omega_max away from zero so `segments` can't divide by it.
+        let profile = TrapezoidalProfile::plan(Delta(10.0), Velocity(0.0), 5.0, None);
+        let segments = profile.segments();
+
+        assert!(!segments.is_empty());
+        for (delta, factor) in &segments {
+            assert!(delta.0.is_finite(), "delta was NaN/inf");
+            assert!(factor.0.is_finite(), "factor was NaN/inf");
+        }
+    }
+}